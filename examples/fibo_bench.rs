@@ -45,7 +45,7 @@ fn bench(c: &mut Criterion<Perf>) {
 
 criterion_group!(
     name = instructions_bench;
-    config = Criterion::default().with_measurement(Perf::new(Builder::from_hardware_event(Hardware::Instructions)));
+    config = Criterion::default().with_measurement(Perf::with_label(Builder::from_hardware_event(Hardware::Instructions), "instructions"));
     targets = bench
 );
 criterion_main!(instructions_bench);
@@ -27,32 +27,62 @@
 //!
 //! criterion_group!(
 //!     name = my_bench;
-//!     config = Criterion::default().with_measurement(Perf::new(Builder::from_hardware_event(Hardware::CacheMisses)));
+//!     config = Criterion::default().with_measurement(Perf::with_label(Builder::from_hardware_event(Hardware::CacheMisses), "cache-misses"));
 //!     targets = bench
 //! );
 //! criterion_main!(my_bench);
 //! ```
 
+extern crate libc;
 extern crate perfcnt;
 
 use criterion::{
     measurement::{Measurement, ValueFormatter},
     Throughput,
 };
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::os::unix::io::AsRawFd;
 
 use perfcnt::linux::PerfCounter;
 use perfcnt::linux::PerfCounterBuilderLinux;
 use perfcnt::AbstractPerfCounter;
 
 /// `perf` implements `criterion::measurement::Measurement` so it can be used in criterion to measure perf events.
-/// Create a struct via `Perf::new()`.
+/// Create a struct via `Perf::new()` or `Perf::with_label()`.
 pub struct Perf {
     counter: RefCell<PerfCounter>,
+    formatter: PerfFormatter,
+    /// `(time_enabled, time_running)` in nanoseconds from the last `end()` call, so callers can tell
+    /// whether (and by how much) the kernel had to time-multiplex this counter. See
+    /// `Perf::was_multiplexed` and `Perf::multiplexing_factor`.
+    last_timing: Cell<(u64, u64)>,
+}
+
+/// Controls which threads and CPUs `Perf` counts events for.
+///
+/// Defaults to [`PerfScope::CurrentProcess`], matching this crate's historical behaviour of only
+/// counting the benchmark's main thread.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PerfScope {
+    /// Count only the calling process/thread, as `Perf` has always done.
+    CurrentProcess,
+    /// Count the calling process and any thread or process it spawns for the duration of the
+    /// benchmark (sets the perf `inherit` flag). Use this for benchmarks that use `rayon` or
+    /// spawn worker threads.
+    CurrentProcessAndChildren,
+    /// Count every process running on the given CPU, regardless of which process it belongs to.
+    /// Useful for CPU-wide counting when you pin the benchmark to that CPU yourself.
+    Cpu(usize),
+}
+
+impl Default for PerfScope {
+    fn default() -> Self {
+        PerfScope::CurrentProcess
+    }
 }
 
 impl Perf {
-    /// Creates a new criterion measurement plugin that measures perf events.
+    /// Creates a new criterion measurement plugin that measures perf events for the current process.
     ///
     /// # Argument
     ///
@@ -61,16 +91,252 @@ impl Perf {
     /// # Remarks
     ///
     /// Should only fail if you select a counter that is not available on your system or you do not have the necessarry access rights.
-    pub fn new(mut builder: PerfCounterBuilderLinux) -> Perf {
-        Perf {
-            counter: RefCell::new(
-                builder
-                    .for_pid(std::process::id() as i32)
-                    .disable()
-                    .finish()
-                    .expect("Could not create counter"),
-            ),
+    ///
+    /// Reports use the generic unit `"events"`. Use `Perf::with_label()` if you want the report to name the
+    /// event you actually selected (e.g. `"instructions"`). Use `Perf::new_with_scope()` if you need to
+    /// count more than just the current process's main thread, or `Perf::with_label_and_scope()` for both.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the counter cannot be created, e.g. the selected event is unsupported or you lack the
+    /// necessary access rights. Use `Perf::try_new()` to handle this case instead of aborting.
+    pub fn new(builder: PerfCounterBuilderLinux) -> Perf {
+        Perf::new_with_scope(builder, PerfScope::CurrentProcess)
+    }
+
+    /// Fallible version of `Perf::new()` that reports counter creation failures instead of panicking.
+    pub fn try_new(builder: PerfCounterBuilderLinux) -> Result<Perf, PerfError> {
+        Perf::try_new_with_scope(builder, PerfScope::CurrentProcess)
+    }
+
+    /// Creates a new criterion measurement plugin that measures perf events, labelling the reported values
+    /// with `label` instead of the generic `"events"`.
+    ///
+    /// # Argument
+    ///
+    /// * `builder` - A PerfCounterBuilderLinux from the crate perfcnt that is configured for the selected counter.
+    /// * `label` - The unit to print in reports and throughput output, e.g. `"instructions"` or `"cache-misses"`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the counter cannot be created, e.g. the selected event is unsupported or you lack the
+    /// necessary access rights. Use `Perf::try_with_label()` to handle this case instead of aborting.
+    pub fn with_label(builder: PerfCounterBuilderLinux, label: &'static str) -> Perf {
+        Perf::with_label_and_scope(builder, label, PerfScope::CurrentProcess)
+    }
+
+    /// Fallible version of `Perf::with_label()` that reports counter creation failures instead of panicking.
+    pub fn try_with_label(
+        builder: PerfCounterBuilderLinux,
+        label: &'static str,
+    ) -> Result<Perf, PerfError> {
+        Perf::try_with_label_and_scope(builder, label, PerfScope::CurrentProcess)
+    }
+
+    /// Creates a new criterion measurement plugin that measures perf events over the given `scope`,
+    /// e.g. including child threads/processes or counting CPU-wide instead of just the current process.
+    ///
+    /// # Argument
+    ///
+    /// * `builder` - A PerfCounterBuilderLinux from the crate perfcnt that is configured for the selected counter.
+    /// * `scope` - Which threads/processes and CPUs to count events for, see [`PerfScope`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the counter cannot be created, e.g. the selected event is unsupported or you lack the
+    /// necessary access rights. Use `Perf::try_new_with_scope()` to handle this case instead of aborting.
+    pub fn new_with_scope(builder: PerfCounterBuilderLinux, scope: PerfScope) -> Perf {
+        Perf::with_label_and_scope(builder, "events", scope)
+    }
+
+    /// Fallible version of `Perf::new_with_scope()` that reports counter creation failures instead of
+    /// panicking.
+    pub fn try_new_with_scope(
+        builder: PerfCounterBuilderLinux,
+        scope: PerfScope,
+    ) -> Result<Perf, PerfError> {
+        Perf::try_with_label_and_scope(builder, "events", scope)
+    }
+
+    /// Creates a new criterion measurement plugin that measures perf events over the given `scope`,
+    /// labelling the reported values with `label` instead of the generic `"events"`. This is the
+    /// combination of `Perf::with_label()` and `Perf::new_with_scope()`, e.g. for counting `Instructions`
+    /// labelled `"instructions"` across a `rayon` thread pool via `PerfScope::CurrentProcessAndChildren`.
+    ///
+    /// # Argument
+    ///
+    /// * `builder` - A PerfCounterBuilderLinux from the crate perfcnt that is configured for the selected counter.
+    /// * `label` - The unit to print in reports and throughput output, e.g. `"instructions"` or `"cache-misses"`.
+    /// * `scope` - Which threads/processes and CPUs to count events for, see [`PerfScope`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the counter cannot be created, e.g. the selected event is unsupported or you lack the
+    /// necessary access rights. Use `Perf::try_with_label_and_scope()` to handle this case instead of
+    /// aborting.
+    pub fn with_label_and_scope(
+        builder: PerfCounterBuilderLinux,
+        label: &'static str,
+        scope: PerfScope,
+    ) -> Perf {
+        Perf::build(builder, scope, label).expect("Could not create counter")
+    }
+
+    /// Fallible version of `Perf::with_label_and_scope()` that reports counter creation failures instead
+    /// of panicking.
+    pub fn try_with_label_and_scope(
+        builder: PerfCounterBuilderLinux,
+        label: &'static str,
+        scope: PerfScope,
+    ) -> Result<Perf, PerfError> {
+        Perf::build(builder, scope, label)
+    }
+
+    fn build(
+        mut builder: PerfCounterBuilderLinux,
+        scope: PerfScope,
+        label: &'static str,
+    ) -> Result<Perf, PerfError> {
+        match scope {
+            PerfScope::CurrentProcess => {
+                builder.for_pid(std::process::id() as i32);
+            }
+            PerfScope::CurrentProcessAndChildren => {
+                builder.for_pid(std::process::id() as i32).inherit();
+            }
+            PerfScope::Cpu(cpu) => {
+                builder.for_all_pids().for_cpu(cpu as isize);
+            }
+        }
+
+        let counter = builder
+            .time_enabled()
+            .time_running()
+            .disable()
+            .finish()
+            .map_err(|_| PerfError::CounterCreation)?;
+
+        Ok(Perf {
+            counter: RefCell::new(counter),
+            formatter: PerfFormatter::new(label),
+            last_timing: Cell::new((0, 0)),
+        })
+    }
+
+    /// Returns whether the kernel had to time-multiplex the counter during the most recent
+    /// measurement (the last `end()` call), i.e. the last reported value is a scaled estimate (see
+    /// `raw_count * time_enabled / time_running`) rather than an exact count. This happens when
+    /// more events are requested than the CPU has hardware PMU counters for.
+    pub fn was_multiplexed(&self) -> bool {
+        let (time_enabled, time_running) = self.last_timing.get();
+        time_running != 0 && time_running != time_enabled
+    }
+
+    /// Returns the `time_enabled / time_running` factor applied to the most recent measurement's raw
+    /// count, so callers can judge how skewed the scaled estimate might be, not just whether scaling
+    /// happened at all (see `Perf::was_multiplexed`). Returns `1.0` when the counter wasn't multiplexed.
+    pub fn multiplexing_factor(&self) -> f64 {
+        let (time_enabled, time_running) = self.last_timing.get();
+        if time_running == 0 {
+            1.0
+        } else {
+            time_enabled as f64 / time_running as f64
+        }
+    }
+
+    fn try_start(&self) -> Result<(), PerfError> {
+        self.counter
+            .borrow()
+            .start()
+            .map_err(|_| PerfError::Start)
+    }
+
+    fn try_end(&self) -> Result<u64, PerfError> {
+        self.counter.borrow().stop().map_err(|_| PerfError::Stop)?;
+        let reading = ScaledReading::read_from(&self.counter.borrow())?;
+        self.last_timing
+            .set((reading.time_enabled, reading.time_running));
+        let scaled = reading.scale();
+        self.counter
+            .borrow_mut()
+            .reset()
+            .map_err(|_| PerfError::Reset)?;
+        Ok(scaled)
+    }
+}
+
+/// Errors that can occur when creating or using a `Perf` or `PerfRatio` measurement.
+///
+/// These generally mean perf is unavailable in the current environment: the selected event is not
+/// supported by the CPU, or the process lacks `CAP_PERFMON` / a permissive enough
+/// `/proc/sys/kernel/perf_event_paranoid`.
+#[derive(Debug)]
+pub enum PerfError {
+    /// Creating the underlying perf counter failed.
+    CounterCreation,
+    /// Starting (enabling) a perf counter failed.
+    Start,
+    /// Stopping (disabling) a perf counter failed.
+    Stop,
+    /// Reading a perf counter's value failed.
+    Read,
+    /// Resetting a perf counter failed.
+    Reset,
+}
+
+impl std::fmt::Display for PerfError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let msg = match self {
+            PerfError::CounterCreation => {
+                "could not create perf counter (unsupported event, or missing CAP_PERFMON / too restrictive perf_event_paranoid)"
+            }
+            PerfError::Start => "could not start perf counter",
+            PerfError::Stop => "could not stop perf counter",
+            PerfError::Read => "could not read perf counter",
+            PerfError::Reset => "could not reset perf counter",
+        };
+        f.write_str(msg)
+    }
+}
+
+impl std::error::Error for PerfError {}
+
+/// The layout `read(2)` returns for a `PerfCounter` configured with
+/// `PERF_FORMAT_TOTAL_TIME_ENABLED | PERF_FORMAT_TOTAL_TIME_RUNNING`: the raw counter value
+/// followed by how long (in nanoseconds) the counter was enabled and actually scheduled on a PMU.
+#[repr(C)]
+#[derive(Default)]
+struct ScaledReading {
+    value: u64,
+    time_enabled: u64,
+    time_running: u64,
+}
+
+impl ScaledReading {
+    fn read_from(counter: &PerfCounter) -> Result<ScaledReading, PerfError> {
+        let mut reading = ScaledReading::default();
+        let len = std::mem::size_of::<ScaledReading>();
+        let n = unsafe {
+            libc::read(
+                counter.as_raw_fd(),
+                &mut reading as *mut ScaledReading as *mut libc::c_void,
+                len,
+            )
+        };
+        if n != len as isize {
+            return Err(PerfError::Read);
+        }
+        Ok(reading)
+    }
+
+    /// Scales `value` for the time it was multiplexed out, falling back to the raw count when
+    /// `time_running` is zero (no samples yet) to avoid a divide-by-zero.
+    fn scale(&self) -> u64 {
+        if self.time_running == 0 || self.time_running == self.time_enabled {
+            return self.value;
         }
+
+        (self.value as u128 * self.time_enabled as u128 / self.time_running as u128) as u64
     }
 }
 
@@ -79,28 +345,319 @@ impl Measurement for Perf {
     type Value = u64;
 
     fn start(&self) -> Self::Intermediate {
-        self.counter
-            .borrow()
-            .start()
-            .expect("Could not read perf counter");
+        self.try_start().unwrap_or_else(|e| panic!("{e}"));
         0
     }
 
     fn end(&self, _i: Self::Intermediate) -> Self::Value {
-        self.counter
+        self.try_end().unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    fn add(&self, v1: &Self::Value, v2: &Self::Value) -> Self::Value {
+        v1 + v2
+    }
+
+    fn zero(&self) -> Self::Value {
+        0
+    }
+
+    fn to_f64(&self, value: &Self::Value) -> f64 {
+        *value as f64
+    }
+
+    fn formatter(&self) -> &dyn ValueFormatter {
+        &self.formatter
+    }
+}
+
+struct PerfFormatter {
+    /// Unit printed alongside values and throughputs, e.g. `"events"`, `"instructions"` or `"cache-misses"`.
+    label: &'static str,
+    /// `"{label}/byte"`, `"{label}/kilobyte"`, ... precomputed once so `scale_throughputs` doesn't have
+    /// to allocate (and leak) a fresh string on every call.
+    byte_unit: &'static str,
+    kilobyte_unit: &'static str,
+    megabyte_unit: &'static str,
+    gigabyte_unit: &'static str,
+    element_unit: &'static str,
+}
+
+impl PerfFormatter {
+    fn new(label: &'static str) -> PerfFormatter {
+        PerfFormatter {
+            label,
+            byte_unit: Box::leak(format!("{label}/byte").into_boxed_str()),
+            kilobyte_unit: Box::leak(format!("{label}/kilobyte").into_boxed_str()),
+            megabyte_unit: Box::leak(format!("{label}/megabyte").into_boxed_str()),
+            gigabyte_unit: Box::leak(format!("{label}/gigabyte").into_boxed_str()),
+            element_unit: Box::leak(format!("{label}/element").into_boxed_str()),
+        }
+    }
+}
+
+impl ValueFormatter for PerfFormatter {
+    fn format_value(&self, value: f64) -> String {
+        format!("{value:.4} {}", self.label)
+    }
+
+    fn format_throughput(&self, throughput: &Throughput, value: f64) -> String {
+        match throughput {
+            Throughput::Bytes(bytes) => {
+                format!("{:.4} {}/byte", value / *bytes as f64, self.label)
+            }
+            Throughput::BytesDecimal(bytes) => {
+                let event_per_byte = value / *bytes as f64;
+
+                let (denominator, unit) = if *bytes < 1000 {
+                    (1.0, "byte")
+                } else if *bytes < 1000 * 1000 {
+                    (1000.0, "kilobyte")
+                } else if *bytes < 1000 * 1000 * 1000 {
+                    (1000.0 * 1000.0, "megabyte")
+                } else {
+                    (1000.0 * 1000.0 * 1000.0, "gigabyte")
+                };
+
+                format!(
+                    "{:.4} {}/{}",
+                    event_per_byte / denominator,
+                    self.label,
+                    unit
+                )
+            }
+            Throughput::Elements(bytes) => {
+                format!("{:.4} {}/element", value / *bytes as f64, self.label)
+            }
+        }
+    }
+
+    fn scale_values(&self, _typical_value: f64, _values: &mut [f64]) -> &'static str {
+        self.label
+    }
+
+    fn scale_throughputs(
+        &self,
+        _typical_value: f64,
+        throughput: &Throughput,
+        values: &mut [f64],
+    ) -> &'static str {
+        match throughput {
+            Throughput::Bytes(bytes) => {
+                for val in values {
+                    *val /= *bytes as f64;
+                }
+                self.byte_unit
+            }
+            Throughput::BytesDecimal(bytes) => {
+                let bytes_per_second = *bytes;
+                let (denominator, unit) = if bytes_per_second < 1000 {
+                    (1.0, self.byte_unit)
+                } else if bytes_per_second < 1000 * 1000 {
+                    (1000.0, self.kilobyte_unit)
+                } else if bytes_per_second < 1000 * 1000 * 1000 {
+                    (1000.0 * 1000.0, self.megabyte_unit)
+                } else {
+                    (1000.0 * 1000.0 * 1000.0, self.gigabyte_unit)
+                };
+
+                for val in values {
+                    *val /= *bytes as f64;
+                    *val /= denominator;
+                }
+
+                unit
+            }
+            Throughput::Elements(bytes) => {
+                for val in values {
+                    *val /= *bytes as f64;
+                }
+                self.element_unit
+            }
+        }
+    }
+
+    fn scale_for_machines(&self, _values: &mut [f64]) -> &'static str {
+        self.label
+    }
+}
+
+/// `PerfRatio` reads a derived ratio of two perf events, e.g. instructions-per-cycle or cache-miss rate.
+///
+/// The two events are opened in a single perf event group so the kernel starts/stops them atomically,
+/// which is what makes the ratio meaningful: both counters cover exactly the same interval even under
+/// scheduling or multiplexing. Create a struct via `PerfRatio::new()`.
+///
+/// Unlike `Perf`, `PerfRatio` does **not** implement `criterion::measurement::Measurement`. Criterion's
+/// analysis fits a through-origin regression of a measurement's `to_f64` value against each sample's
+/// iteration count, assuming that value grows linearly with `iters` (as a raw event count does). A ratio
+/// doesn't: the numerator and denominator each grow linearly, but their quotient stays roughly constant
+/// regardless of batch size, so reporting `numerator / denominator` through that pipeline would get
+/// divided by the iteration count a second time and come out as nonsense for any benchmark that isn't
+/// pinned to exactly one iteration per sample. Call `start()`/`end()` directly around the code you want
+/// to measure instead, and read the ratio off the returned `(numerator, denominator)` pair yourself.
+pub struct PerfRatio {
+    numerator: RefCell<PerfCounter>,
+    denominator: RefCell<PerfCounter>,
+    label: &'static str,
+}
+
+impl PerfRatio {
+    /// Creates a `PerfRatio` that reads the ratio `numerator / denominator` of two perf events, e.g.
+    /// `Instructions / CPUCycles` for IPC.
+    ///
+    /// # Argument
+    ///
+    /// * `numerator_builder` - A PerfCounterBuilderLinux configured for the event to use as the ratio's numerator.
+    /// * `denominator_builder` - A PerfCounterBuilderLinux configured for the event to use as the ratio's denominator.
+    /// * `label` - The unit to print alongside the ratio, e.g. `"IPC"`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either counter cannot be created, e.g. the selected event is unsupported or you lack the
+    /// necessary access rights. Use `PerfRatio::try_new()` to handle this case instead of aborting.
+    pub fn new(
+        numerator_builder: PerfCounterBuilderLinux,
+        denominator_builder: PerfCounterBuilderLinux,
+        label: &'static str,
+    ) -> PerfRatio {
+        PerfRatio::try_new(numerator_builder, denominator_builder, label)
+            .expect("Could not create counter")
+    }
+
+    /// Fallible version of `PerfRatio::new()` that reports counter creation failures instead of
+    /// panicking.
+    pub fn try_new(
+        mut numerator_builder: PerfCounterBuilderLinux,
+        mut denominator_builder: PerfCounterBuilderLinux,
+        label: &'static str,
+    ) -> Result<PerfRatio, PerfError> {
+        let pid = std::process::id() as i32;
+
+        let numerator = numerator_builder
+            .for_pid(pid)
+            .disable()
+            .finish()
+            .map_err(|_| PerfError::CounterCreation)?;
+
+        // Only the group leader is created disabled: a group member is scheduled only while its
+        // leader is enabled, so enabling/disabling the leader starts/stops the whole group at once.
+        let denominator = denominator_builder
+            .for_pid(pid)
+            .group_leader(numerator.as_raw_fd())
+            .finish()
+            .map_err(|_| PerfError::CounterCreation)?;
+
+        Ok(PerfRatio {
+            numerator: RefCell::new(numerator),
+            denominator: RefCell::new(denominator),
+            label,
+        })
+    }
+
+    /// Starts both counters in the group atomically.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the counters cannot be started. Use `PerfRatio::try_start()` to handle this case
+    /// instead of aborting.
+    pub fn start(&self) {
+        self.try_start().unwrap_or_else(|e| panic!("{e}"));
+    }
+
+    /// Fallible version of `PerfRatio::start()` that reports the failure instead of panicking.
+    pub fn try_start(&self) -> Result<(), PerfError> {
+        self.numerator
+            .borrow()
+            .start()
+            .map_err(|_| PerfError::Start)
+    }
+
+    /// Stops both counters, returning `(numerator, denominator)` summed since the last `start()`, then
+    /// resets both for the next `start()`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if stopping, reading, or resetting either counter fails. Use `PerfRatio::try_end()` to
+    /// handle this case instead of aborting.
+    pub fn end(&self) -> (u64, u64) {
+        self.try_end().unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Fallible version of `PerfRatio::end()` that reports the failure instead of panicking.
+    pub fn try_end(&self) -> Result<(u64, u64), PerfError> {
+        self.numerator
             .borrow()
             .stop()
-            .expect("Could not stop perf counter");
-        let ret = self
-            .counter
+            .map_err(|_| PerfError::Stop)?;
+
+        let numerator = self
+            .numerator
             .borrow_mut()
             .read()
-            .expect("Could not read perf counter");
-        self.counter
+            .map_err(|_| PerfError::Read)?;
+        let denominator = self
+            .denominator
+            .borrow_mut()
+            .read()
+            .map_err(|_| PerfError::Read)?;
+
+        self.numerator
             .borrow_mut()
             .reset()
-            .expect("Could not reset perf counter");
-        ret
+            .map_err(|_| PerfError::Reset)?;
+        self.denominator
+            .borrow_mut()
+            .reset()
+            .map_err(|_| PerfError::Reset)?;
+
+        Ok((numerator, denominator))
+    }
+
+    /// Formats a `(numerator, denominator)` reading from `end()` as `"{ratio:.4} {label}"`.
+    pub fn format_ratio(&self, reading: (u64, u64)) -> String {
+        format!(
+            "{:.4} {}",
+            reading.0 as f64 / reading.1 as f64,
+            self.label
+        )
+    }
+}
+
+#[cfg(target_arch = "x86")]
+use core::arch::x86::{__cpuid, __rdtscp, _rdtsc};
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::{__cpuid, __rdtscp, _rdtsc};
+
+/// `CyclesPerByte` measures wall-clock CPU cycles using the x86/x86_64 `rdtsc`/`rdtscp` instructions
+/// instead of the Linux perf interface, so it works without `CAP_PERFMON` or a permissive
+/// `perf_event_paranoid`. Use it as a drop-in alternative to `Perf` on machines where perf access isn't
+/// available, e.g. inside a restricted container. Create a struct via `CyclesPerByte`.
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+pub struct CyclesPerByte;
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+impl Measurement for CyclesPerByte {
+    type Intermediate = u64;
+    type Value = u64;
+
+    fn start(&self) -> Self::Intermediate {
+        // `cpuid` is a serializing instruction, so it drains the pipeline before `rdtsc` reads the
+        // timestamp counter, preventing the code under measurement from being reordered ahead of it.
+        unsafe {
+            __cpuid(0);
+            _rdtsc()
+        }
+    }
+
+    fn end(&self, start: Self::Intermediate) -> Self::Value {
+        let mut aux: u32 = 0;
+        // `rdtscp` waits for prior instructions to retire before reading the counter; the trailing
+        // `cpuid` then stops later instructions from being reordered ahead of the read.
+        let end = unsafe { __rdtscp(&mut aux) };
+        unsafe {
+            __cpuid(0);
+        }
+        end - start
     }
 
     fn add(&self, v1: &Self::Value, v2: &Self::Value) -> Self::Value {
@@ -116,41 +673,43 @@ impl Measurement for Perf {
     }
 
     fn formatter(&self) -> &dyn ValueFormatter {
-        &PerfFormatter
+        &CyclesFormatter
     }
 }
 
-struct PerfFormatter;
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+struct CyclesFormatter;
 
-impl ValueFormatter for PerfFormatter {
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+impl ValueFormatter for CyclesFormatter {
     fn format_value(&self, value: f64) -> String {
         format!("{value:.4} cycles")
     }
 
     fn format_throughput(&self, throughput: &Throughput, value: f64) -> String {
         match throughput {
-            Throughput::Bytes(bytes) => format!("{:.4} events/byte", value / *bytes as f64),
+            Throughput::Bytes(bytes) => format!("{:.4} cycles/byte", value / *bytes as f64),
             Throughput::BytesDecimal(bytes) => {
-                let event_per_byte = value / *bytes as f64;
+                let cycles_per_byte = value / *bytes as f64;
 
                 let (denominator, unit) = if *bytes < 1000 {
-                    (1.0, "events/byte")
+                    (1.0, "cycles/byte")
                 } else if *bytes < 1000 * 1000 {
-                    (1000.0, "events/kilobyte")
+                    (1000.0, "cycles/kilobyte")
                 } else if *bytes < 1000 * 1000 * 1000 {
-                    (1000.0 * 1000.0, "events/megabyte")
+                    (1000.0 * 1000.0, "cycles/megabyte")
                 } else {
-                    (1000.0 * 1000.0 * 1000.0, "events/gigabyte")
+                    (1000.0 * 1000.0 * 1000.0, "cycles/gigabyte")
                 };
 
-                format!("{:.4} {}", event_per_byte / denominator, unit)
+                format!("{:.4} {}", cycles_per_byte / denominator, unit)
             }
-            Throughput::Elements(bytes) => format!("{:.4} events/element", value / *bytes as f64),
+            Throughput::Elements(bytes) => format!("{:.4} cycles/element", value / *bytes as f64),
         }
     }
 
     fn scale_values(&self, _typical_value: f64, _values: &mut [f64]) -> &'static str {
-        "events"
+        "cycles"
     }
 
     fn scale_throughputs(
@@ -164,18 +723,18 @@ impl ValueFormatter for PerfFormatter {
                 for val in values {
                     *val /= *bytes as f64;
                 }
-                "events/byte"
+                "cycles/byte"
             }
             Throughput::BytesDecimal(bytes) => {
                 let bytes_per_second = *bytes;
                 let (denominator, unit) = if bytes_per_second < 1000 {
-                    (1.0, "events/byte")
+                    (1.0, "cycles/byte")
                 } else if bytes_per_second < 1000 * 1000 {
-                    (1000.0, "events/kilobyte")
+                    (1000.0, "cycles/kilobyte")
                 } else if bytes_per_second < 1000 * 1000 * 1000 {
-                    (1000.0 * 1000.0, "events/megabyte")
+                    (1000.0 * 1000.0, "cycles/megabyte")
                 } else {
-                    (1000.0 * 1000.0 * 1000.0, "events/gigabyte")
+                    (1000.0 * 1000.0 * 1000.0, "cycles/gigabyte")
                 };
 
                 for val in values {
@@ -189,12 +748,12 @@ impl ValueFormatter for PerfFormatter {
                 for val in values {
                     *val /= *bytes as f64;
                 }
-                "events/element"
+                "cycles/element"
             }
         }
     }
 
     fn scale_for_machines(&self, _values: &mut [f64]) -> &'static str {
-        "events"
+        "cycles"
     }
 }